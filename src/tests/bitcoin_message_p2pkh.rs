@@ -0,0 +1,139 @@
+use super::fixtures::{build_resolved_tx, gen_tx};
+use super::{DummyDataLoader, BITCOIN_MESSAGE_P2PKH_BIN, MAX_CYCLES};
+use ckb_core::{
+    transaction::{Transaction, TransactionBuilder},
+    Bytes,
+};
+use ckb_crypto::secp::{Generator, Privkey, Pubkey};
+use ckb_script::{ScriptConfig, TransactionScriptsVerifier};
+
+// Signs the tx hash the way an unmodified Bitcoin wallet's "Sign Message"
+// feature would: over the "Bitcoin Signed Message:\n" envelope rather than
+// the raw hash. `compressed` controls both the header byte's flag bit and
+// which pubkey encoding the signer publishes.
+pub fn sign_tx_bitcoin_message(tx: Transaction, key: &Privkey, compressed: bool) -> Transaction {
+    let digest = bitcoin_message_digest(&tx.hash());
+    let message = secp256k1::Message::from_slice(digest.as_bytes()).expect("message");
+    let context = &ckb_crypto::secp::SECP256K1;
+    let secret_key = secp256k1::key::SecretKey::from_slice(key.as_bytes()).expect("secret key");
+    let recoverable_sig = context.sign_recoverable(&message, &secret_key);
+    let (recovery_id, sig) = recoverable_sig.serialize_compact();
+    let mut header = 27 + recovery_id.to_i32() as u8;
+    if compressed {
+        header += 4;
+    }
+    let mut witness = vec![header];
+    witness.extend_from_slice(&sig);
+    TransactionBuilder::from_transaction(tx)
+        .witnesses_clear()
+        .witness(vec![Bytes::from(witness)])
+        .build()
+}
+
+fn bitcoin_message_digest(tx_hash: &numext_fixed_hash::H256) -> numext_fixed_hash::H256 {
+    let hex_hash = format!("{:x}", tx_hash).into_bytes();
+    let magic = b"Bitcoin Signed Message:\n";
+    let mut preimage = Vec::with_capacity(1 + magic.len() + 1 + hex_hash.len());
+    preimage.push(magic.len() as u8);
+    preimage.extend_from_slice(magic);
+    preimage.push(hex_hash.len() as u8);
+    preimage.extend_from_slice(&hex_hash);
+    sha256d(&preimage)
+}
+
+fn sha256d(data: &[u8]) -> numext_fixed_hash::H256 {
+    use sha2::{Digest, Sha256};
+    let round1 = Sha256::digest(data);
+    let digest: [u8; 32] = Sha256::digest(&round1).into();
+    numext_fixed_hash::H256::from(&digest)
+}
+
+fn pubkey_uncompressed(pubkey: &Pubkey) -> Vec<u8> {
+    let mut serialized = vec![4u8; 65];
+    serialized[1..65].copy_from_slice(pubkey.as_ref());
+    serialized
+}
+
+fn pubkey_compressed(pubkey: &Pubkey) -> Vec<u8> {
+    pubkey.serialize()
+}
+
+fn pubkey_hash(serialized_pubkey: &[u8]) -> Vec<u8> {
+    use ripemd160::{Digest as _, Ripemd160};
+    use sha2::{Digest as _, Sha256};
+    let sha256_hash = Sha256::digest(serialized_pubkey);
+    Ripemd160::digest(&sha256_hash).to_vec()
+}
+
+#[test]
+fn test_bitcoin_message_unlock_compressed() {
+    let mut data_loader = DummyDataLoader::new();
+    let key_gen = Generator::new();
+    let privkey = key_gen.random_privkey();
+    let pubkey = pubkey_compressed(&privkey.pubkey().expect("pubkey"));
+    let pubkey_hash = pubkey_hash(&pubkey);
+    let tx = gen_tx(
+        &mut data_loader,
+        BITCOIN_MESSAGE_P2PKH_BIN.clone(),
+        vec![pubkey_hash.into()],
+        vec![],
+    );
+    let tx = sign_tx_bitcoin_message(tx, &privkey, true);
+    let resolved_tx = build_resolved_tx(&data_loader, &tx);
+    let script_config = ScriptConfig::default();
+    let verify_result = TransactionScriptsVerifier::new(&resolved_tx, &data_loader, &script_config)
+        .verify(MAX_CYCLES);
+    verify_result.expect("pass verification");
+}
+
+#[test]
+fn test_bitcoin_message_unlock_uncompressed() {
+    let mut data_loader = DummyDataLoader::new();
+    let key_gen = Generator::new();
+    let privkey = key_gen.random_privkey();
+    let pubkey = pubkey_uncompressed(&privkey.pubkey().expect("pubkey"));
+    let pubkey_hash = pubkey_hash(&pubkey);
+    let tx = gen_tx(
+        &mut data_loader,
+        BITCOIN_MESSAGE_P2PKH_BIN.clone(),
+        vec![pubkey_hash.into()],
+        vec![],
+    );
+    let tx = sign_tx_bitcoin_message(tx, &privkey, false);
+    let resolved_tx = build_resolved_tx(&data_loader, &tx);
+    let script_config = ScriptConfig::default();
+    let verify_result = TransactionScriptsVerifier::new(&resolved_tx, &data_loader, &script_config)
+        .verify(MAX_CYCLES);
+    verify_result.expect("pass verification");
+}
+
+#[test]
+fn test_bitcoin_message_unlock_wrong_recid() {
+    let mut data_loader = DummyDataLoader::new();
+    let key_gen = Generator::new();
+    let privkey = key_gen.random_privkey();
+    let pubkey = pubkey_compressed(&privkey.pubkey().expect("pubkey"));
+    let pubkey_hash = pubkey_hash(&pubkey);
+    let tx = gen_tx(
+        &mut data_loader,
+        BITCOIN_MESSAGE_P2PKH_BIN.clone(),
+        vec![pubkey_hash.into()],
+        vec![],
+    );
+    let tx = sign_tx_bitcoin_message(tx, &privkey, true);
+    // Flip the recovery id encoded in the header byte (31 + recid for a
+    // compressed key), keeping the compressed flag, so the recovered pubkey
+    // no longer matches the one committed to in `lock_args`.
+    let witness = tx.witnesses()[0][0].clone();
+    let mut corrupted = witness.to_vec();
+    corrupted[0] = 31 + ((corrupted[0] - 31) ^ 1);
+    let tx = TransactionBuilder::from_transaction(tx)
+        .witnesses_clear()
+        .witness(vec![Bytes::from(corrupted)])
+        .build();
+    let resolved_tx = build_resolved_tx(&data_loader, &tx);
+    let script_config = ScriptConfig::default();
+    let verify_result = TransactionScriptsVerifier::new(&resolved_tx, &data_loader, &script_config)
+        .verify(MAX_CYCLES);
+    assert!(verify_result.is_err());
+}
@@ -0,0 +1,120 @@
+use super::fixtures::{build_resolved_tx, gen_tx};
+use super::{DummyDataLoader, ETH_RECOVERABLE_BIN, MAX_CYCLES};
+use ckb_core::{
+    transaction::{CellOutput, Transaction, TransactionBuilder},
+    Bytes,
+};
+use ckb_crypto::secp::{Generator, Privkey, Pubkey};
+use ckb_script::{ScriptConfig, TransactionScriptsVerifier};
+
+fn keccak256(data: &[u8]) -> [u8; 32] {
+    use tiny_keccak::{Hasher, Keccak};
+    let mut hasher = Keccak::v256();
+    let mut digest = [0u8; 32];
+    hasher.update(data);
+    hasher.finalize(&mut digest);
+    digest
+}
+
+fn pubkey_uncompressed(pubkey: &Pubkey) -> Vec<u8> {
+    let mut serialized = vec![4u8; 65];
+    serialized[1..65].copy_from_slice(pubkey.as_ref());
+    serialized
+}
+
+fn eth_address(pubkey: &Pubkey) -> Vec<u8> {
+    let uncompressed = pubkey_uncompressed(pubkey);
+    let digest = keccak256(&uncompressed[1..]);
+    digest[12..32].to_vec()
+}
+
+// Signs the tx hash the way an Ethereum wallet's `personal_sign` would,
+// producing the `\x19Ethereum Signed Message:\n32`-prefixed digest this
+// lock expects.
+pub fn sign_tx_eth(tx: Transaction, key: &Privkey) -> Transaction {
+    let mut preimage = b"\x19Ethereum Signed Message:\n32".to_vec();
+    preimage.extend_from_slice(tx.hash().as_bytes());
+    let digest = keccak256(&preimage);
+
+    let context = &ckb_crypto::secp::SECP256K1;
+    let message = secp256k1::Message::from_slice(&digest).expect("message");
+    let secret_key = secp256k1::key::SecretKey::from_slice(key.as_bytes()).expect("secret key");
+    let recoverable_sig = context.sign_recoverable(&message, &secret_key);
+    let (recovery_id, sig) = recoverable_sig.serialize_compact();
+
+    let mut witness = sig.to_vec();
+    witness.push(recovery_id.to_i32() as u8);
+    TransactionBuilder::from_transaction(tx)
+        .witnesses_clear()
+        .witness(vec![Bytes::from(witness)])
+        .build()
+}
+
+#[test]
+fn test_eth_unlock() {
+    let mut data_loader = DummyDataLoader::new();
+    let key_gen = Generator::new();
+    let privkey = key_gen.random_privkey();
+    let address = eth_address(&privkey.pubkey().expect("pubkey"));
+    let tx = gen_tx(
+        &mut data_loader,
+        ETH_RECOVERABLE_BIN.clone(),
+        vec![address.into()],
+        vec![],
+    );
+    let tx = sign_tx_eth(tx, &privkey);
+    let resolved_tx = build_resolved_tx(&data_loader, &tx);
+    let script_config = ScriptConfig::default();
+    let verify_result = TransactionScriptsVerifier::new(&resolved_tx, &data_loader, &script_config)
+        .verify(MAX_CYCLES);
+    verify_result.expect("pass verification");
+}
+
+#[test]
+fn test_eth_unlock_wrong_key() {
+    let mut data_loader = DummyDataLoader::new();
+    let key_gen = Generator::new();
+    let privkey = key_gen.random_privkey();
+    let wrong_privkey = key_gen.random_privkey();
+    let address = eth_address(&privkey.pubkey().expect("pubkey"));
+    let tx = gen_tx(
+        &mut data_loader,
+        ETH_RECOVERABLE_BIN.clone(),
+        vec![address.into()],
+        vec![],
+    );
+    let tx = sign_tx_eth(tx, &wrong_privkey);
+    let resolved_tx = build_resolved_tx(&data_loader, &tx);
+    let script_config = ScriptConfig::default();
+    let verify_result = TransactionScriptsVerifier::new(&resolved_tx, &data_loader, &script_config)
+        .verify(MAX_CYCLES);
+    assert!(verify_result.is_err());
+}
+
+#[test]
+fn test_eth_unlock_mutated_tx_hash() {
+    let mut data_loader = DummyDataLoader::new();
+    let key_gen = Generator::new();
+    let privkey = key_gen.random_privkey();
+    let address = eth_address(&privkey.pubkey().expect("pubkey"));
+    let tx = gen_tx(
+        &mut data_loader,
+        ETH_RECOVERABLE_BIN.clone(),
+        vec![address.into()],
+        vec![],
+    );
+    let tx = sign_tx_eth(tx, &privkey);
+    let tx = TransactionBuilder::from_transaction(tx)
+        .output(CellOutput::new(
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            None,
+        ))
+        .build();
+    let resolved_tx = build_resolved_tx(&data_loader, &tx);
+    let script_config = ScriptConfig::default();
+    let verify_result = TransactionScriptsVerifier::new(&resolved_tx, &data_loader, &script_config)
+        .verify(MAX_CYCLES);
+    assert!(verify_result.is_err());
+}
@@ -0,0 +1,117 @@
+// Shared cell/transaction scaffolding for the recoverable-signature lock
+// test modules (bitcoin_message_p2pkh, eth_unlock, tron_unlock,
+// multisig_unlock). Each of those locks is exercised against an otherwise
+// identical single-input transaction, so the fixture lives here once
+// instead of being pasted into every test file.
+use super::{DummyDataLoader, SECP256K1_DATA_BIN};
+use ckb_core::{
+    cell::{CellMetaBuilder, ResolvedTransaction},
+    script::{Script, ScriptHashType},
+    transaction::{CellDep, CellInput, CellOutput, OutPoint, Transaction, TransactionBuilder},
+    Bytes, Capacity,
+};
+use rand::{thread_rng, Rng};
+
+pub fn gen_tx(
+    dummy: &mut DummyDataLoader,
+    script_data: Bytes,
+    lock_args: Vec<Bytes>,
+    extra_witness: Vec<Bytes>,
+) -> Transaction {
+    let previous_tx_hash = {
+        let mut rng = thread_rng();
+        let mut buf = [0u8; 32];
+        rng.fill(&mut buf);
+        buf.into()
+    };
+    let previous_out_point = OutPoint::new(previous_tx_hash, 0);
+    let capacity = Capacity::shannons(42);
+    let contract_tx_hash = {
+        let mut rng = thread_rng();
+        let mut buf = [0u8; 32];
+        rng.fill(&mut buf);
+        buf.into()
+    };
+    let contract_out_point = OutPoint::new(contract_tx_hash, 0);
+    let dep_cell = CellOutput::new(
+        Capacity::bytes(script_data.len()).expect("script capacity"),
+        CellOutput::calculate_data_hash(&script_data),
+        Default::default(),
+        None,
+    );
+    let dep_cell_data_hash = dep_cell.data_hash().to_owned();
+    dummy
+        .cells
+        .insert(contract_out_point.clone(), (dep_cell, script_data));
+    let secp256k1_data_out_point = {
+        let tx_hash = {
+            let mut rng = thread_rng();
+            let mut buf = [0u8; 32];
+            rng.fill(&mut buf);
+            buf.into()
+        };
+        OutPoint::new(tx_hash, 0)
+    };
+    let secp256k1_data_cell = CellOutput::new(
+        Capacity::bytes(SECP256K1_DATA_BIN.len()).expect("data capacity"),
+        CellOutput::calculate_data_hash(&SECP256K1_DATA_BIN),
+        Default::default(),
+        None,
+    );
+    dummy.cells.insert(
+        secp256k1_data_out_point.clone(),
+        (secp256k1_data_cell, SECP256K1_DATA_BIN.clone()),
+    );
+    let previous_output_cell = CellOutput::new(
+        capacity,
+        Default::default(),
+        Script::new(lock_args, dep_cell_data_hash, ScriptHashType::Data),
+        None,
+    );
+    dummy.cells.insert(
+        previous_out_point.clone(),
+        (previous_output_cell, Bytes::new()),
+    );
+    TransactionBuilder::default()
+        .input(CellInput::new(previous_out_point.clone(), 0))
+        .witness(extra_witness)
+        .cell_dep(CellDep::new(contract_out_point, false))
+        .cell_dep(CellDep::new(secp256k1_data_out_point, false))
+        .output(CellOutput::new(
+            capacity,
+            Default::default(),
+            Default::default(),
+            None,
+        ))
+        .output_data(Bytes::new())
+        .build()
+}
+
+pub fn build_resolved_tx<'a>(
+    data_loader: &DummyDataLoader,
+    tx: &'a Transaction,
+) -> ResolvedTransaction<'a> {
+    let previous_out_point = tx.inputs()[0].previous_output.clone();
+    let resolved_cell_deps = tx
+        .cell_deps()
+        .iter()
+        .map(|dep| {
+            let deps_out_point = dep.clone();
+            let (dep_output, dep_data) =
+                data_loader.cells.get(&deps_out_point.out_point()).unwrap();
+            CellMetaBuilder::from_cell_output(dep_output.to_owned(), dep_data.to_owned())
+                .out_point(deps_out_point.out_point().clone())
+                .build()
+        })
+        .collect();
+    let (input_output, input_data) = data_loader.cells.get(&previous_out_point).unwrap();
+    let input_cell =
+        CellMetaBuilder::from_cell_output(input_output.to_owned(), input_data.to_owned())
+            .out_point(previous_out_point)
+            .build();
+    ResolvedTransaction {
+        transaction: tx,
+        resolved_cell_deps,
+        resolved_inputs: vec![input_cell],
+    }
+}
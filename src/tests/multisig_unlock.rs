@@ -0,0 +1,162 @@
+use super::fixtures::{build_resolved_tx, gen_tx};
+use super::{DummyDataLoader, MAX_CYCLES, MULTISIG_BIN};
+use ckb_core::{
+    transaction::{Transaction, TransactionBuilder},
+    Bytes,
+};
+use ckb_crypto::secp::{Generator, Privkey, Pubkey};
+use ckb_script::{ScriptConfig, TransactionScriptsVerifier};
+
+fn pubkey_hash(pubkey: &Pubkey) -> Vec<u8> {
+    use ripemd160::{Digest as _, Ripemd160};
+    use sha2::{Digest as _, Sha256};
+    let sha256_hash = Sha256::digest(&pubkey.serialize());
+    Ripemd160::digest(&sha256_hash).to_vec()
+}
+
+// `[reserved, m, n]` header followed by `n` 20-byte pubkey hashes, matching
+// what multisig.c expects in `lock_args`.
+fn build_multisig_args(m: u8, pubkey_hashes: &[Vec<u8>]) -> Vec<u8> {
+    let mut args = vec![0u8, m, pubkey_hashes.len() as u8];
+    for hash in pubkey_hashes {
+        args.extend_from_slice(hash);
+    }
+    args
+}
+
+fn sign_recoverable(key: &Privkey, tx_hash: &numext_fixed_hash::H256) -> Vec<u8> {
+    let context = &ckb_crypto::secp::SECP256K1;
+    let message = secp256k1::Message::from_slice(tx_hash.as_bytes()).expect("message");
+    let secret_key = secp256k1::key::SecretKey::from_slice(key.as_bytes()).expect("secret key");
+    let recoverable_sig = context.sign_recoverable(&message, &secret_key);
+    let (recovery_id, sig) = recoverable_sig.serialize_compact();
+    let mut signature = sig.to_vec();
+    signature.push(recovery_id.to_i32() as u8);
+    signature
+}
+
+fn args_commitment(args: &[u8]) -> Vec<u8> {
+    use ripemd160::{Digest as _, Ripemd160};
+    use sha2::{Digest as _, Sha256};
+    let sha256_hash = Sha256::digest(args);
+    Ripemd160::digest(&sha256_hash).to_vec()
+}
+
+// Signs the tx hash with each of `keys`, building on the raw-hash-signing
+// convention `sign_tx` already uses. The witness's first slot commits to
+// `args` (the exact multisig config being authorized), followed by the
+// concatenated 65-byte recoverable signatures.
+pub fn sign_tx_multisig(tx: Transaction, args: &[u8], keys: &[Privkey]) -> Transaction {
+    let tx_hash = tx.hash();
+    let mut witness = args_commitment(args);
+    for key in keys {
+        witness.extend_from_slice(&sign_recoverable(key, &tx_hash));
+    }
+    TransactionBuilder::from_transaction(tx)
+        .witnesses_clear()
+        .witness(vec![Bytes::from(witness)])
+        .build()
+}
+
+fn gen_keys(n: usize) -> Vec<Privkey> {
+    let key_gen = Generator::new();
+    (0..n).map(|_| key_gen.random_privkey()).collect()
+}
+
+#[test]
+fn test_multisig_exact_threshold() {
+    let mut data_loader = DummyDataLoader::new();
+    let keys = gen_keys(3);
+    let hashes: Vec<Vec<u8>> = keys
+        .iter()
+        .map(|k| pubkey_hash(&k.pubkey().expect("pubkey")))
+        .collect();
+    let args = build_multisig_args(2, &hashes);
+
+    let tx = gen_tx(
+        &mut data_loader,
+        MULTISIG_BIN.clone(),
+        vec![args.clone().into()],
+        vec![],
+    );
+    let tx = sign_tx_multisig(tx, &args, &keys[0..2]);
+    let resolved_tx = build_resolved_tx(&data_loader, &tx);
+    let script_config = ScriptConfig::default();
+    let verify_result = TransactionScriptsVerifier::new(&resolved_tx, &data_loader, &script_config)
+        .verify(MAX_CYCLES);
+    verify_result.expect("pass verification");
+}
+
+#[test]
+fn test_multisig_too_few_signatures() {
+    let mut data_loader = DummyDataLoader::new();
+    let keys = gen_keys(3);
+    let hashes: Vec<Vec<u8>> = keys
+        .iter()
+        .map(|k| pubkey_hash(&k.pubkey().expect("pubkey")))
+        .collect();
+    let args = build_multisig_args(2, &hashes);
+
+    let tx = gen_tx(
+        &mut data_loader,
+        MULTISIG_BIN.clone(),
+        vec![args.clone().into()],
+        vec![],
+    );
+    let tx = sign_tx_multisig(tx, &args, &keys[0..1]);
+    let resolved_tx = build_resolved_tx(&data_loader, &tx);
+    let script_config = ScriptConfig::default();
+    let verify_result = TransactionScriptsVerifier::new(&resolved_tx, &data_loader, &script_config)
+        .verify(MAX_CYCLES);
+    assert!(verify_result.is_err());
+}
+
+#[test]
+fn test_multisig_duplicate_signature_attack() {
+    let mut data_loader = DummyDataLoader::new();
+    let keys = gen_keys(3);
+    let hashes: Vec<Vec<u8>> = keys
+        .iter()
+        .map(|k| pubkey_hash(&k.pubkey().expect("pubkey")))
+        .collect();
+    let args = build_multisig_args(2, &hashes);
+
+    let tx = gen_tx(
+        &mut data_loader,
+        MULTISIG_BIN.clone(),
+        vec![args.clone().into()],
+        vec![],
+    );
+    // Same key signs twice instead of two distinct keys.
+    let tx = sign_tx_multisig(tx, &args, &[keys[0].clone(), keys[0].clone()]);
+    let resolved_tx = build_resolved_tx(&data_loader, &tx);
+    let script_config = ScriptConfig::default();
+    let verify_result = TransactionScriptsVerifier::new(&resolved_tx, &data_loader, &script_config)
+        .verify(MAX_CYCLES);
+    assert!(verify_result.is_err());
+}
+
+#[test]
+fn test_multisig_signature_from_key_not_in_set() {
+    let mut data_loader = DummyDataLoader::new();
+    let keys = gen_keys(3);
+    let outsider = Generator::new().random_privkey();
+    let hashes: Vec<Vec<u8>> = keys
+        .iter()
+        .map(|k| pubkey_hash(&k.pubkey().expect("pubkey")))
+        .collect();
+    let args = build_multisig_args(2, &hashes);
+
+    let tx = gen_tx(
+        &mut data_loader,
+        MULTISIG_BIN.clone(),
+        vec![args.clone().into()],
+        vec![],
+    );
+    let tx = sign_tx_multisig(tx, &args, &[keys[0].clone(), outsider]);
+    let resolved_tx = build_resolved_tx(&data_loader, &tx);
+    let script_config = ScriptConfig::default();
+    let verify_result = TransactionScriptsVerifier::new(&resolved_tx, &data_loader, &script_config)
+        .verify(MAX_CYCLES);
+    assert!(verify_result.is_err());
+}